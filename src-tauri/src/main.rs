@@ -1,25 +1,45 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use keyring::{Entry, Error as KeyringError};
+mod polling;
+mod secret_store;
+mod updater;
+
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::sync::{Mutex, OnceLock};
-use tauri::menu::{Menu, MenuItem};
+use tauri::menu::{IsMenuItem, Menu, MenuItem, PredefinedMenuItem};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
 use tauri::{image::Image, Emitter, Manager, WindowEvent};
+#[cfg(not(target_os = "macos"))]
+use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_store::StoreExt;
 use url::Url;
 
-const KEYRING_SERVICE: &str = "gitlab-action-radar";
-const KEYRING_ACCOUNT: &str = "gitlab-pat";
 const TRAY_ID: &str = "main-tray";
 const NOTIFICATION_OPEN_TAB_EVENT: &str = "notification-open-tab";
+const SETTINGS_STORE_FILE: &str = "settings.json";
+const VISIBLE_ON_ALL_WORKSPACES_KEY: &str = "visibleOnAllWorkspaces";
+const ALWAYS_ON_TOP_KEY: &str = "alwaysOnTop";
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
-struct TrayIndicatorPayload {
-    conflict_count: u32,
-    failed_ci_count: u32,
-    review_pending_count: u32,
-    actionable_total_count: u32,
+pub(crate) struct TrayIndicatorPayload {
+    pub(crate) conflict_count: u32,
+    pub(crate) failed_ci_count: u32,
+    pub(crate) review_pending_count: u32,
+    pub(crate) actionable_total_count: u32,
+    #[serde(default)]
+    pub(crate) entries: Vec<ActionableMrEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ActionableMrEntry {
+    title: String,
+    category: String,
+    web_url: String,
+    open_tab: String,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -30,17 +50,149 @@ struct ClickableNotificationPayload {
     open_tab: String,
 }
 
+#[derive(Debug, Clone)]
+struct MrMenuAction {
+    web_url: String,
+    open_tab: String,
+}
+
+fn mr_menu_actions_slot() -> &'static Mutex<HashMap<String, MrMenuAction>> {
+    static SLOT: OnceLock<Mutex<HashMap<String, MrMenuAction>>> = OnceLock::new();
+    SLOT.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
 fn pending_notification_tab_slot() -> &'static Mutex<Option<String>> {
     static SLOT: OnceLock<Mutex<Option<String>>> = OnceLock::new();
     SLOT.get_or_init(|| Mutex::new(None))
 }
 
+fn last_tray_payload_slot() -> &'static Mutex<TrayIndicatorPayload> {
+    static SLOT: OnceLock<Mutex<TrayIndicatorPayload>> = OnceLock::new();
+    SLOT.get_or_init(|| {
+        Mutex::new(TrayIndicatorPayload {
+            conflict_count: 0,
+            failed_ci_count: 0,
+            review_pending_count: 0,
+            actionable_total_count: 0,
+            entries: Vec::new(),
+        })
+    })
+}
+
+/// The most recent payload rendered to the tray, including the actionable MR
+/// entries. Background updaters (the poller, the updater badge) that don't
+/// have a fresh entry list of their own read this first so they don't wipe
+/// out the per-MR menu with an empty one.
+pub(crate) fn last_tray_payload() -> TrayIndicatorPayload {
+    last_tray_payload_slot()
+        .lock()
+        .map(|payload| payload.clone())
+        .unwrap_or_else(|_| TrayIndicatorPayload {
+            conflict_count: 0,
+            failed_ci_count: 0,
+            review_pending_count: 0,
+            actionable_total_count: 0,
+            entries: Vec::new(),
+        })
+}
+
 fn set_pending_notification_tab(tab: String) {
     if let Ok(mut slot) = pending_notification_tab_slot().lock() {
         *slot = Some(tab);
     }
 }
 
+/// Derives a menu item id that stays stable for a given MR across menu
+/// rebuilds, so a click racing a rebuild (the tray menu redraws on every
+/// poll tick) can't land on a different, unrelated MR. Using the entry's
+/// position in the list would let a removed entry's old id get silently
+/// reassigned to whatever now occupies that index; hashing `web_url`
+/// (unique per MR) instead means a removed entry's id simply stops
+/// resolving.
+fn mr_menu_item_id(entry: &ActionableMrEntry) -> String {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    entry.web_url.hash(&mut hasher);
+    format!("mr:{:x}", hasher.finish())
+}
+
+/// Rebuilds the tray `Menu` from the current actionable MR list, grouping
+/// entries by category with a separator between groups and "Quit" pinned at
+/// the bottom. Menu item ids are derived from a stable per-MR key (see
+/// `mr_menu_item_id`), and the id -> action mapping used by the click
+/// handler is replaced atomically so stale ids from a previous build never
+/// resolve.
+fn build_tray_menu(
+    app: &tauri::AppHandle,
+    entries: &[ActionableMrEntry],
+) -> tauri::Result<Menu<tauri::Wry>> {
+    let mut categories: Vec<&str> = Vec::new();
+    for entry in entries {
+        if !categories.contains(&entry.category.as_str()) {
+            categories.push(entry.category.as_str());
+        }
+    }
+
+    let mut items: Vec<Box<dyn IsMenuItem<tauri::Wry>>> = Vec::new();
+    let mut actions = HashMap::new();
+
+    for category in categories {
+        let header = MenuItem::with_id(
+            app,
+            format!("mr-category:{category}"),
+            category,
+            false,
+            None::<&str>,
+        )?;
+        items.push(Box::new(header));
+
+        for entry in entries.iter().filter(|entry| entry.category == category) {
+            let id = mr_menu_item_id(entry);
+            let item = MenuItem::with_id(app, &id, &entry.title, true, None::<&str>)?;
+            items.push(Box::new(item));
+            actions.insert(
+                id,
+                MrMenuAction {
+                    web_url: entry.web_url.clone(),
+                    open_tab: entry.open_tab.clone(),
+                },
+            );
+        }
+
+        items.push(Box::new(PredefinedMenuItem::separator(app)?));
+    }
+
+    let update_label = if updater::is_update_available() {
+        "Install Update"
+    } else {
+        "Check for Updates"
+    };
+    items.push(Box::new(MenuItem::with_id(
+        app,
+        "check-for-updates",
+        update_label,
+        true,
+        None::<&str>,
+    )?));
+    items.push(Box::new(PredefinedMenuItem::separator(app)?));
+
+    items.push(Box::new(MenuItem::with_id(
+        app,
+        "quit",
+        "Quit",
+        true,
+        None::<&str>,
+    )?));
+
+    let refs: Vec<&dyn IsMenuItem<tauri::Wry>> = items.iter().map(|item| item.as_ref()).collect();
+    let menu = Menu::with_items(app, &refs)?;
+
+    if let Ok(mut slot) = mr_menu_actions_slot().lock() {
+        *slot = actions;
+    }
+
+    Ok(menu)
+}
+
 fn set_pixel(rgba: &mut [u8], width: u32, x: u32, y: u32, color: [u8; 4]) {
     let index = ((y * width + x) * 4) as usize;
     rgba[index] = color[0];
@@ -130,60 +282,48 @@ fn create_tray_icon_image(
 }
 
 fn create_tray_tooltip(payload: &TrayIndicatorPayload) -> String {
-    if payload.actionable_total_count == 0 {
-        return "GitLab Action Radar: 対応が必要なMRはありません".to_string();
-    }
-
-    format!(
-        "GitLab Action Radar: 合計{}件（競合 {} / CI失敗 {} / レビュー待ち {}）",
-        payload.actionable_total_count,
-        payload.conflict_count,
-        payload.failed_ci_count,
-        payload.review_pending_count
-    )
-}
+    let base = if payload.actionable_total_count == 0 {
+        "GitLab Action Radar: 対応が必要なMRはありません".to_string()
+    } else {
+        format!(
+            "GitLab Action Radar: 合計{}件（競合 {} / CI失敗 {} / レビュー待ち {}）",
+            payload.actionable_total_count,
+            payload.conflict_count,
+            payload.failed_ci_count,
+            payload.review_pending_count
+        )
+    };
 
-fn keyring_entry() -> Result<Entry, String> {
-    Entry::new(KEYRING_SERVICE, KEYRING_ACCOUNT)
-        .map_err(|err| format!("failed to access secure storage: {err}"))
+    if updater::is_update_available() {
+        format!("{base}\n新しいバージョンが利用可能です")
+    } else {
+        base
+    }
 }
 
 #[tauri::command]
-fn save_pat(token: String) -> Result<(), String> {
+fn save_pat(app: tauri::AppHandle, token: String) -> Result<(), String> {
     let trimmed = token.trim();
     if trimmed.is_empty() {
         return Err("PAT is empty".to_string());
     }
 
-    let entry = keyring_entry()?;
-    entry
-        .set_password(trimmed)
-        .map_err(|err| format!("failed to store PAT: {err}"))
+    secret_store::SecretStore::save(&app, trimmed)?;
+    Ok(())
 }
 
 #[tauri::command]
-fn load_pat() -> Result<Option<String>, String> {
-    let entry = keyring_entry()?;
-    match entry.get_password() {
-        Ok(token) => Ok(Some(token)),
-        Err(KeyringError::NoEntry) => Ok(None),
-        Err(err) => Err(format!("failed to load PAT: {err}")),
-    }
+pub(crate) fn load_pat(app: tauri::AppHandle) -> Result<secret_store::LoadPatResult, String> {
+    secret_store::SecretStore::load(&app)
 }
 
 #[tauri::command]
-fn clear_pat() -> Result<(), String> {
-    let entry = keyring_entry()?;
-    match entry.delete_credential() {
-        Ok(_) => Ok(()),
-        Err(KeyringError::NoEntry) => Ok(()),
-        Err(err) => Err(format!("failed to clear PAT: {err}")),
-    }
+fn clear_pat(app: tauri::AppHandle) -> Result<(), String> {
+    secret_store::SecretStore::clear(&app)
 }
 
-#[tauri::command]
-fn open_external_url(url: String) -> Result<(), String> {
-    let parsed = Url::parse(&url).map_err(|_| "invalid URL".to_string())?;
+fn open_external_url_inner(url: &str) -> Result<(), String> {
+    let parsed = Url::parse(url).map_err(|_| "invalid URL".to_string())?;
     if parsed.scheme() != "http" && parsed.scheme() != "https" {
         return Err("only http/https URLs are supported".to_string());
     }
@@ -191,6 +331,11 @@ fn open_external_url(url: String) -> Result<(), String> {
     open::that_detached(parsed.as_str()).map_err(|err| format!("failed to open URL: {err}"))
 }
 
+#[tauri::command]
+fn open_external_url(url: String) -> Result<(), String> {
+    open_external_url_inner(&url)
+}
+
 #[tauri::command]
 fn open_notification_settings() -> Result<(), String> {
     #[cfg(target_os = "macos")]
@@ -229,6 +374,46 @@ fn show_main_window(app: tauri::AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+fn apply_visible_on_all_workspaces(app: &tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "main window not found".to_string())?;
+    window
+        .set_visible_on_all_workspaces(enabled)
+        .map_err(|err| format!("failed to set visible-on-all-workspaces: {err}"))
+}
+
+fn apply_always_on_top(app: &tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "main window not found".to_string())?;
+    window
+        .set_always_on_top(enabled)
+        .map_err(|err| format!("failed to set always-on-top: {err}"))
+}
+
+fn persist_window_pref(app: &tauri::AppHandle, key: &str, enabled: bool) -> Result<(), String> {
+    let store = app
+        .store(SETTINGS_STORE_FILE)
+        .map_err(|err| format!("failed to open settings store: {err}"))?;
+    store.set(key, serde_json::json!(enabled));
+    store
+        .save()
+        .map_err(|err| format!("failed to persist setting: {err}"))
+}
+
+#[tauri::command]
+fn set_visible_on_all_workspaces(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    apply_visible_on_all_workspaces(&app, enabled)?;
+    persist_window_pref(&app, VISIBLE_ON_ALL_WORKSPACES_KEY, enabled)
+}
+
+#[tauri::command]
+fn set_always_on_top(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    apply_always_on_top(&app, enabled)?;
+    persist_window_pref(&app, ALWAYS_ON_TOP_KEY, enabled)
+}
+
 #[tauri::command]
 fn send_clickable_notification(
     app: tauri::AppHandle,
@@ -268,9 +453,21 @@ fn send_clickable_notification(
 
     #[cfg(not(target_os = "macos"))]
     {
-        let _ = app;
-        let _ = payload;
-        Err("clickable notifications are only supported on macOS".to_string())
+        app.notification()
+            .builder()
+            .title(&payload.title)
+            .body(&payload.body)
+            .show()
+            .map_err(|err| format!("failed to send notification: {err}"))?;
+
+        // tauri_plugin_notification does not report back to Rust which action
+        // (or whether) the user clicked on Windows/Linux, so unlike the macOS
+        // branch above there's no real click to gate the tab switch on, and no
+        // pending tab is set here. Focusing the main window unconditionally would yank it to the
+        // foreground on every notification (e.g. every CI failure), not just
+        // ones the user acted on, so this branch just sends the OS toast and
+        // leaves the window alone.
+        Ok(())
     }
 }
 
@@ -283,7 +480,7 @@ fn take_pending_notification_tab() -> Option<String> {
 }
 
 #[tauri::command]
-fn update_tray_indicator(
+pub(crate) fn update_tray_indicator(
     app: tauri::AppHandle,
     payload: TrayIndicatorPayload,
 ) -> Result<(), String> {
@@ -291,6 +488,10 @@ fn update_tray_indicator(
         .tray_by_id(TRAY_ID)
         .ok_or_else(|| "tray icon not found".to_string())?;
 
+    if let Ok(mut slot) = last_tray_payload_slot().lock() {
+        *slot = payload.clone();
+    }
+
     tray.set_icon(Some(create_tray_icon_image(
         payload.conflict_count,
         payload.failed_ci_count,
@@ -313,6 +514,11 @@ fn update_tray_indicator(
     let _ = tray.set_title(title.as_deref());
     let _ = tray.set_tooltip(Some(create_tray_tooltip(&payload)));
 
+    let menu = build_tray_menu(&app, &payload.entries)
+        .map_err(|err| format!("failed to build tray menu: {err}"))?;
+    tray.set_menu(Some(menu))
+        .map_err(|err| format!("failed to update tray menu: {err}"))?;
+
     Ok(())
 }
 
@@ -320,6 +526,8 @@ fn main() {
     tauri::Builder::default()
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_store::Builder::default().build())
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .manage(polling::PollingState::default())
         .invoke_handler(tauri::generate_handler![
             save_pat,
             load_pat,
@@ -329,11 +537,16 @@ fn main() {
             show_main_window,
             send_clickable_notification,
             take_pending_notification_tab,
-            update_tray_indicator
+            update_tray_indicator,
+            set_visible_on_all_workspaces,
+            set_always_on_top,
+            polling::start_polling,
+            polling::stop_polling,
+            updater::check_for_update,
+            updater::install_update
         ])
         .setup(|app| {
-            let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&quit_item])?;
+            let menu = build_tray_menu(app.handle(), &[])?;
 
             let _tray = TrayIconBuilder::with_id(TRAY_ID)
                 .icon(create_tray_icon_image(0, 0, 0))
@@ -359,8 +572,36 @@ fn main() {
                     }
                 })
                 .on_menu_event(|app, event| {
-                    if event.id == "quit" {
+                    let id = event.id.as_ref();
+                    if id == "quit" {
                         app.exit(0);
+                        return;
+                    }
+
+                    if id == "check-for-updates" {
+                        let app_handle = app.clone();
+                        tokio::spawn(async move {
+                            if updater::is_update_available() {
+                                let _ = updater::install_update(app_handle).await;
+                            } else {
+                                let _ = updater::check_for_update(app_handle).await;
+                            }
+                        });
+                        return;
+                    }
+
+                    let action = mr_menu_actions_slot()
+                        .lock()
+                        .ok()
+                        .and_then(|actions| actions.get(id).cloned());
+
+                    if let Some(action) = action {
+                        let _ = open_external_url_inner(&action.web_url);
+                        if action.open_tab == "assigned" || action.open_tab == "review" {
+                            set_pending_notification_tab(action.open_tab.clone());
+                            let _ = app.emit(NOTIFICATION_OPEN_TAB_EVENT, action.open_tab.clone());
+                        }
+                        let _ = show_main_window(app.clone());
                     }
                 })
                 .build(app)?;
@@ -372,8 +613,24 @@ fn main() {
                         api.prevent_close();
                     }
                 });
+
+                let app_handle = app.handle();
+                let store = app_handle.store(SETTINGS_STORE_FILE)?;
+                let visible_on_all_workspaces = store
+                    .get(VISIBLE_ON_ALL_WORKSPACES_KEY)
+                    .and_then(|value| value.as_bool())
+                    .unwrap_or(false);
+                let always_on_top = store
+                    .get(ALWAYS_ON_TOP_KEY)
+                    .and_then(|value| value.as_bool())
+                    .unwrap_or(false);
+
+                let _ = apply_visible_on_all_workspaces(app_handle, visible_on_all_workspaces);
+                let _ = apply_always_on_top(app_handle, always_on_top);
             }
 
+            updater::start_background_checks(app.handle().clone());
+
             Ok(())
         })
         .run(tauri::generate_context!())
@@ -0,0 +1,223 @@
+//! Rust-side GitLab polling so the tray stays up to date even while the main
+//! window is hidden, and so polling can honor corporate HTTP(S)/SOCKS
+//! proxies the webview has no control over.
+
+use crate::{load_pat, update_tray_indicator};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Emitter;
+use url::Url;
+
+const POLLING_UPDATED_EVENT: &str = "polling-updated";
+
+/// Connect/request timeout for GitLab calls. Without this, a single hung
+/// request (dead proxy, stalled TLS handshake, unresponsive self-hosted
+/// instance) would block the poll loop forever, since every tick runs
+/// sequentially on the one background task.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// `merge_requests` page size; pages are walked until a short page ends the
+/// listing, so org-wide instances with more than one page of open MRs are
+/// still counted in full.
+const MERGE_REQUESTS_PAGE_SIZE: &str = "100";
+
+#[derive(Default)]
+pub(crate) struct PollingState {
+    handle: Mutex<Option<tokio::task::JoinHandle<()>>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PollResult {
+    conflict_count: u32,
+    failed_ci_count: u32,
+    review_pending_count: u32,
+}
+
+/// Builds the HTTP client used for polling. reqwest reads `HTTPS_PROXY` /
+/// `ALL_PROXY` (including `socks5://` URLs, once the `socks` feature is
+/// enabled) from the environment automatically, so self-hosted GitLab behind
+/// a corporate proxy keeps working with no extra configuration.
+fn build_http_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .user_agent(concat!("gitlab-action-radar/", env!("CARGO_PKG_VERSION")))
+        .connect_timeout(REQUEST_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|err| format!("failed to build HTTP client: {err}"))
+}
+
+/// Walks every page of `GET /merge_requests` instead of trusting the first
+/// one, since self-hosted instances with more than a page of org-wide open
+/// MRs would otherwise silently undercount conflicts/failed CI/review
+/// pending with no error.
+async fn fetch_all_merge_requests(
+    client: &reqwest::Client,
+    gitlab_base_url: &str,
+    pat: &str,
+) -> Result<Vec<serde_json::Value>, String> {
+    let mut merge_requests = Vec::new();
+    let mut page = 1u32;
+
+    loop {
+        let page_str = page.to_string();
+        let response: Vec<serde_json::Value> = client
+            .get(format!("{gitlab_base_url}/api/v4/merge_requests"))
+            .query(&[
+                ("scope", "all"),
+                ("state", "opened"),
+                ("per_page", MERGE_REQUESTS_PAGE_SIZE),
+                ("page", page_str.as_str()),
+            ])
+            .header("PRIVATE-TOKEN", pat)
+            .send()
+            .await
+            .map_err(|err| format!("failed to reach GitLab: {err}"))?
+            .error_for_status()
+            .map_err(|err| format!("GitLab returned an error: {err}"))?
+            .json()
+            .await
+            .map_err(|err| format!("failed to parse GitLab response: {err}"))?;
+
+        let is_last_page = response.len() < MERGE_REQUESTS_PAGE_SIZE.parse::<usize>().unwrap();
+        merge_requests.extend(response);
+
+        if is_last_page {
+            break;
+        }
+        page += 1;
+    }
+
+    Ok(merge_requests)
+}
+
+async fn fetch_actionable_counts(
+    client: &reqwest::Client,
+    gitlab_base_url: &str,
+    pat: &str,
+) -> Result<PollResult, String> {
+    let merge_requests = fetch_all_merge_requests(client, gitlab_base_url, pat).await?;
+
+    let mut conflict_count = 0u32;
+    let mut failed_ci_count = 0u32;
+    let mut review_pending_count = 0u32;
+
+    for merge_request in &merge_requests {
+        // "need_rebase" means the MR conflicts with its target branch and
+        // needs a manual rebase — that's a conflict condition, not a review
+        // one, so it's bucketed alongside `merge_status == cannot_be_merged`
+        // rather than into review_pending_count.
+        if merge_request["merge_status"] == "cannot_be_merged"
+            || merge_request["detailed_merge_status"] == "need_rebase"
+        {
+            conflict_count += 1;
+        }
+        if merge_request["head_pipeline"]["status"] == "failed" {
+            failed_ci_count += 1;
+        }
+        // `approved_by` only exists on the dedicated
+        // `/merge_requests/:iid/approvals` response, not on this list
+        // endpoint, so it always reads as missing here and never actually
+        // distinguishes anything. `detailed_merge_status` is present on the
+        // list payload and GitLab sets it to "not_approved" precisely when
+        // the MR's required approvals aren't satisfied yet, which is what
+        // review_pending_count is meant to track.
+        if merge_request["detailed_merge_status"] == "not_approved" {
+            review_pending_count += 1;
+        }
+    }
+
+    Ok(PollResult {
+        conflict_count,
+        failed_ci_count,
+        review_pending_count,
+    })
+}
+
+fn stop_polling_inner(state: &PollingState) {
+    if let Ok(mut handle) = state.handle.lock() {
+        if let Some(join_handle) = handle.take() {
+            join_handle.abort();
+        }
+    }
+}
+
+#[tauri::command]
+pub(crate) fn start_polling(
+    app: tauri::AppHandle,
+    state: tauri::State<PollingState>,
+    gitlab_base_url: String,
+    poll_interval_seconds: u64,
+) -> Result<(), String> {
+    let parsed = Url::parse(&gitlab_base_url).map_err(|_| "invalid GitLab base URL".to_string())?;
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        return Err("only http/https GitLab base URLs are supported".to_string());
+    }
+    if poll_interval_seconds == 0 {
+        return Err("poll interval must be greater than zero".to_string());
+    }
+
+    stop_polling_inner(&state);
+
+    let client = build_http_client()?;
+    let base_url = parsed.as_str().trim_end_matches('/').to_string();
+    let app_handle = app.clone();
+
+    let join_handle = tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(poll_interval_seconds));
+        loop {
+            ticker.tick().await;
+
+            let pat = match load_pat(app_handle.clone()) {
+                Ok(result) => match result.token {
+                    Some(pat) => pat,
+                    None => continue,
+                },
+                Err(_) => continue,
+            };
+
+            match fetch_actionable_counts(&client, &base_url, &pat).await {
+                Ok(result) => {
+                    // Keep whatever per-MR entries the tray menu was last built
+                    // with (usually set by the frontend) — this poller only
+                    // knows aggregate counts, so feeding update_tray_indicator
+                    // an empty entry list would otherwise wipe out the menu on
+                    // every tick.
+                    //
+                    // This means the per-MR menu can only go as fresh as the
+                    // last time the window was open and pushed a new list:
+                    // the tooltip counts are accurate as of this tick, but an
+                    // individual menu entry can point at an MR that's since
+                    // been merged or closed until the window is opened again.
+                    // There's no bound on how stale that gets today short of
+                    // reopening the window.
+                    let mut payload = crate::last_tray_payload();
+                    payload.conflict_count = result.conflict_count;
+                    payload.failed_ci_count = result.failed_ci_count;
+                    payload.review_pending_count = result.review_pending_count;
+                    payload.actionable_total_count = result.conflict_count
+                        + result.failed_ci_count
+                        + result.review_pending_count;
+
+                    let _ = update_tray_indicator(app_handle.clone(), payload.clone());
+                    let _ = app_handle.emit(POLLING_UPDATED_EVENT, payload);
+                }
+                Err(err) => {
+                    let _ = app_handle.emit(POLLING_UPDATED_EVENT, err);
+                }
+            }
+        }
+    });
+
+    if let Ok(mut handle) = state.handle.lock() {
+        *handle = Some(join_handle);
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn stop_polling(state: tauri::State<PollingState>) {
+    stop_polling_inner(&state);
+}
@@ -0,0 +1,229 @@
+//! Keyring-first, encrypted-file-fallback storage for the GitLab PAT.
+//!
+//! `Entry::new`/the platform keyring backend fails hard on Linux machines
+//! with no running Secret Service (headless sessions, minimal WMs), which
+//! otherwise breaks `save_pat`/`load_pat`/`clear_pat` entirely. When that
+//! happens we transparently fall back to an AES-GCM-encrypted file under the
+//! app data dir, keyed off a machine-stable identifier, so the PAT commands
+//! keep working there too.
+
+use aes_gcm::aead::{Aead, OsRng};
+use aes_gcm::{Aes256Gcm, AeadCore, Key, KeyInit, Nonce};
+use keyring::{Entry, Error as KeyringError};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+use tauri::Manager;
+
+const KEYRING_SERVICE: &str = "gitlab-action-radar";
+const KEYRING_ACCOUNT: &str = "gitlab-pat";
+const SECRET_FILE_NAME: &str = "gitlab-pat.enc";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SecretBackend {
+    Keyring,
+    EncryptedFile,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LoadPatResult {
+    pub(crate) token: Option<String>,
+    pub(crate) backend: SecretBackend,
+}
+
+pub(crate) struct SecretStore;
+
+impl SecretStore {
+    pub(crate) fn save(app: &tauri::AppHandle, token: &str) -> Result<SecretBackend, String> {
+        match keyring_entry() {
+            Ok(entry) => match entry.set_password(token) {
+                Ok(()) => {
+                    remove_fallback_file(app)?;
+                    Ok(SecretBackend::Keyring)
+                }
+                Err(err) if is_backend_unavailable(&err) => {
+                    save_to_file(app, token)?;
+                    Ok(SecretBackend::EncryptedFile)
+                }
+                Err(err) => Err(format!("failed to store PAT: {err}")),
+            },
+            Err(err) if is_backend_unavailable(&err) => {
+                save_to_file(app, token)?;
+                Ok(SecretBackend::EncryptedFile)
+            }
+            Err(err) => Err(format!("failed to access secure storage: {err}")),
+        }
+    }
+
+    pub(crate) fn load(app: &tauri::AppHandle) -> Result<LoadPatResult, String> {
+        match keyring_entry() {
+            Ok(entry) => match entry.get_password() {
+                Ok(token) => Ok(LoadPatResult {
+                    token: Some(token),
+                    backend: SecretBackend::Keyring,
+                }),
+                Err(KeyringError::NoEntry) => {
+                    // The keyring itself is reachable and functional here —
+                    // there's just nothing saved in it. Only claim the
+                    // less-secure fallback is active if the fallback file
+                    // actually has something in it; otherwise this is a
+                    // plain "no PAT saved yet" on a fully working keyring.
+                    let token = load_from_file(app)?;
+                    let backend = if token.is_some() {
+                        SecretBackend::EncryptedFile
+                    } else {
+                        SecretBackend::Keyring
+                    };
+                    Ok(LoadPatResult { token, backend })
+                }
+                Err(err) if is_backend_unavailable(&err) => Ok(LoadPatResult {
+                    token: load_from_file(app)?,
+                    backend: SecretBackend::EncryptedFile,
+                }),
+                Err(err) => Err(format!("failed to load PAT: {err}")),
+            },
+            Err(err) if is_backend_unavailable(&err) => Ok(LoadPatResult {
+                token: load_from_file(app)?,
+                backend: SecretBackend::EncryptedFile,
+            }),
+            Err(err) => Err(format!("failed to access secure storage: {err}")),
+        }
+    }
+
+    pub(crate) fn clear(app: &tauri::AppHandle) -> Result<(), String> {
+        match keyring_entry() {
+            Ok(entry) => match entry.delete_credential() {
+                Ok(_) | Err(KeyringError::NoEntry) => {}
+                Err(err) if is_backend_unavailable(&err) => {}
+                Err(err) => return Err(format!("failed to clear PAT: {err}")),
+            },
+            Err(err) if !is_backend_unavailable(&err) => {
+                return Err(format!("failed to access secure storage: {err}"))
+            }
+            Err(_) => {}
+        }
+
+        remove_fallback_file(app)
+    }
+}
+
+/// Removes the encrypted fallback file, if any. A PAT that now lives in the
+/// keyring shouldn't also linger on disk from an earlier session where the
+/// keyring was unavailable.
+fn remove_fallback_file(app: &tauri::AppHandle) -> Result<(), String> {
+    let path = secret_file_path(app)?;
+    if path.exists() {
+        fs::remove_file(&path).map_err(|err| format!("failed to clear PAT file: {err}"))?;
+    }
+    Ok(())
+}
+
+fn keyring_entry() -> Result<Entry, KeyringError> {
+    Entry::new(KEYRING_SERVICE, KEYRING_ACCOUNT)
+}
+
+/// Keyring errors that indicate "no secure storage backend is reachable"
+/// (e.g. no running Secret Service) rather than a real failure to read/write
+/// an existing entry.
+fn is_backend_unavailable(err: &KeyringError) -> bool {
+    matches!(
+        err,
+        KeyringError::NoStorageAccess(_) | KeyringError::PlatformFailure(_)
+    )
+}
+
+fn secret_file_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|err| format!("failed to resolve app data dir: {err}"))?;
+    fs::create_dir_all(&dir).map_err(|err| format!("failed to create app data dir: {err}"))?;
+    Ok(dir.join(SECRET_FILE_NAME))
+}
+
+/// Derives a stable 256-bit key from a machine identifier so the encrypted
+/// PAT file can only be decrypted on the machine that wrote it. When no
+/// machine identifier can be read, a fixed fallback string is used instead,
+/// so the key is then the same on every machine where that happens.
+fn derive_key() -> [u8; 32] {
+    let machine_id =
+        machine_uid::get().unwrap_or_else(|_| "gitlab-action-radar-fallback-machine-id".to_string());
+
+    let mut hasher = Sha256::new();
+    hasher.update(KEYRING_SERVICE.as_bytes());
+    hasher.update(machine_id.as_bytes());
+    hasher.finalize().into()
+}
+
+fn save_to_file(app: &tauri::AppHandle, token: &str) -> Result<(), String> {
+    let key = derive_key();
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    let ciphertext = cipher
+        .encrypt(&nonce, token.as_bytes())
+        .map_err(|err| format!("failed to encrypt PAT: {err}"))?;
+
+    let mut contents = nonce.to_vec();
+    contents.extend_from_slice(&ciphertext);
+
+    let path = secret_file_path(app)?;
+    write_restricted(&path, &contents)?;
+
+    Ok(())
+}
+
+/// Writes `contents` to `path`, owner-only-readable from the moment the file
+/// is created. The key is derived from a machine-wide identifier with no
+/// per-user component, so on a shared machine the encryption alone doesn't
+/// keep other local accounts out — `chmod`-ing after a plain `fs::write`
+/// would leave a window where the default (world/group-readable) mode is in
+/// effect, so the restricted mode is applied at open time instead.
+#[cfg(unix)]
+fn write_restricted(path: &std::path::Path, contents: &[u8]) -> Result<(), String> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+        .map_err(|err| format!("failed to create PAT file: {err}"))?;
+    file.write_all(contents)
+        .map_err(|err| format!("failed to write PAT file: {err}"))
+}
+
+#[cfg(not(unix))]
+fn write_restricted(path: &std::path::Path, contents: &[u8]) -> Result<(), String> {
+    fs::write(path, contents).map_err(|err| format!("failed to write PAT file: {err}"))
+}
+
+fn load_from_file(app: &tauri::AppHandle) -> Result<Option<String>, String> {
+    let path = secret_file_path(app)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let contents =
+        fs::read(&path).map_err(|err| format!("failed to read PAT file: {err}"))?;
+    if contents.len() < 12 {
+        return Err("PAT file is corrupt".to_string());
+    }
+    let (nonce_bytes, ciphertext) = contents.split_at(12);
+
+    let key = derive_key();
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+    let nonce = Nonce::from_slice(nonce_bytes);
+
+    let plaintext = cipher
+        .decrypt(nonce, ciphertext)
+        .map_err(|err| format!("failed to decrypt PAT: {err}"))?;
+
+    String::from_utf8(plaintext)
+        .map(Some)
+        .map_err(|err| format!("PAT file is corrupt: {err}"))
+}
@@ -0,0 +1,96 @@
+//! Background update checks via the Tauri updater plugin. A background tray
+//! tool is easy to forget to update manually, so we surface availability
+//! directly in the tray tooltip/menu instead of relying on the user to go
+//! looking for it.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri_plugin_updater::UpdaterExt;
+
+/// How often to check for updates in the background, on top of the one-shot
+/// check run at launch.
+const BACKGROUND_CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+static UPDATE_AVAILABLE: OnceLock<AtomicBool> = OnceLock::new();
+
+fn update_available_flag() -> &'static AtomicBool {
+    UPDATE_AVAILABLE.get_or_init(|| AtomicBool::new(false))
+}
+
+pub(crate) fn is_update_available() -> bool {
+    update_available_flag().load(Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AvailableUpdate {
+    version: String,
+    notes: Option<String>,
+}
+
+#[tauri::command]
+pub(crate) async fn check_for_update(
+    app: tauri::AppHandle,
+) -> Result<Option<AvailableUpdate>, String> {
+    let updater = app
+        .updater()
+        .map_err(|err| format!("updater unavailable: {err}"))?;
+
+    let result = match updater.check().await {
+        Ok(Some(update)) => {
+            update_available_flag().store(true, Ordering::Relaxed);
+            Ok(Some(AvailableUpdate {
+                version: update.version.clone(),
+                notes: update.body.clone(),
+            }))
+        }
+        Ok(None) => {
+            update_available_flag().store(false, Ordering::Relaxed);
+            Ok(None)
+        }
+        Err(err) => Err(format!("failed to check for update: {err}")),
+    };
+
+    // The flag alone doesn't repaint anything — create_tray_tooltip and
+    // build_tray_menu only consult it when something else happens to call
+    // update_tray_indicator next, which could be minutes away (or never, if
+    // polling isn't running). Refresh the tray immediately so the badge/menu
+    // entry shows up (or clears) as soon as a check completes.
+    let _ = crate::update_tray_indicator(app.clone(), crate::last_tray_payload());
+
+    result
+}
+
+/// Runs `check_for_update` once at launch and then on a fixed interval for
+/// as long as the app is running, so a new release gets surfaced in the tray
+/// without the user ever having to click "Check for Updates" themselves.
+pub(crate) fn start_background_checks(app: tauri::AppHandle) {
+    tokio::spawn(async move {
+        loop {
+            let _ = check_for_update(app.clone()).await;
+            tokio::time::sleep(BACKGROUND_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub(crate) async fn install_update(app: tauri::AppHandle) -> Result<(), String> {
+    let updater = app
+        .updater()
+        .map_err(|err| format!("updater unavailable: {err}"))?;
+
+    let update = updater
+        .check()
+        .await
+        .map_err(|err| format!("failed to check for update: {err}"))?
+        .ok_or_else(|| "no update available".to_string())?;
+
+    update
+        .download_and_install(|_, _| {}, || {})
+        .await
+        .map_err(|err| format!("failed to install update: {err}"))?;
+
+    update_available_flag().store(false, Ordering::Relaxed);
+    app.restart();
+}